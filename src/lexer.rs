@@ -3,11 +3,26 @@
 //! This module contains all lexing related implementations, including the token representation
 //! used by the lexer, the lexer itself, and the unit tests for them.
 
+pub mod cursor;
+pub mod error;
+pub mod span;
 pub mod tokens;
 
+use cursor::Cursor;
+use error::LexError;
+use span::Span;
 use tokens::Token;
 
-use anyhow::Context;
+/// Failure modes of [`Lexer::read_number`], translated into a spanned [`LexError`] by its caller
+/// in [`Lexer::read_token_raw`].
+enum NumberLexError {
+    /// The literal's value does not fit in an `i32` (or, for a float, failed to parse at all).
+    Overflow,
+    /// A `0x`/`0b`/`0o` radix prefix was not followed by at least one valid digit.
+    EmptyRadixLiteral,
+    /// The literal was immediately followed by an identifier character, e.g. `42foo`.
+    AdjacentIdentifier,
+}
 
 /// The lexer for the sol language.
 ///
@@ -15,48 +30,135 @@ use anyhow::Context;
 /// concise and pre-defined meanings. This step is the basis of any compiler, as it makes the task
 /// of parsing, checking and interpreting the input exponentially easier for later steps.
 pub struct Lexer {
-    /// The input string separated into chars.
-    input: Vec<char>,
-    /// The current index of the character being accessed.
-    idx: usize,
-    /// The current line.
-    line: usize,
-    /// The current column.
-    column: usize,
-    /// If the current character is the first being parsed by the lexer or not.
-    ///
-    /// This is needed because we mark the `idx` as the last returned character index, and
-    /// since we use `usize` as its type, we cannot use negative numbers. As such, we need to keep
-    /// track of the case where we didn't return any characters yet, meaning the `idx` is yet not
-    /// representative of the character index.
-    is_first_char: bool,
+    /// The character-access layer, supporting arbitrary lookahead and backtracking.
+    cursor: Cursor,
+    /// Whether automatic semicolon insertion is enabled (see [`Lexer::with_asi`]).
+    asi: bool,
+    /// Whether the token last returned from [`Lexer::read_token`] can legally end a statement,
+    /// used by automatic semicolon insertion to decide whether a line break should synthesize a
+    /// [`Token::Semicolon`].
+    asi_prev_ends_statement: bool,
+    /// A real token already lexed while deciding whether to synthesize an ASI semicolon, to be
+    /// returned on the next call to [`Lexer::read_token`] instead of being lexed again.
+    asi_buffered: Option<(Token, Span)>,
+    /// Whether comments are emitted as tokens instead of skipped (see [`Lexer::with_comments`]).
+    preserve_comments: bool,
 }
 
 impl Lexer {
     /// Create a new lexer instance.
     pub fn new(input: String) -> Self {
         Lexer {
-            input: input.chars().collect(),
-            idx: 0,
-            line: 1,
-            column: 1,
-            is_first_char: true,
+            cursor: Cursor::new(&input),
+            asi: false,
+            asi_prev_ends_statement: false,
+            asi_buffered: None,
+            preserve_comments: false,
+        }
+    }
+
+    /// Create a new lexer instance with automatic semicolon insertion enabled.
+    ///
+    /// When a line break follows a token that can legally end a statement (an [`Token::Ident`],
+    /// [`Token::Integer`]/[`Token::Float`], [`Token::RParen`], [`Token::RBracket`], or
+    /// [`Token::Return`]), [`Lexer::read_token`] synthesizes a zero-width [`Token::Semicolon`]
+    /// before the next real token, so statements don't need an explicit trailing `;`.
+    pub fn with_asi(input: String) -> Self {
+        Lexer {
+            asi: true,
+            ..Self::new(input)
         }
     }
 
-    /// Return the next token in the string.
+    /// Create a new lexer instance that emits comments as tokens instead of skipping them.
     ///
-    /// This returns `Option<Token>` if any token (including invalid strings, represented as
-    /// `Token::Invalid`) was read, and `None` if there was no more content left to lex, meaning
-    /// the end of the input was reached. After that, every call to `read_token()` will return
-    /// `None` as the result.
-    pub fn read_token(&mut self) -> Option<Token> {
+    /// `// ...` and `/* ... */` comments are emitted as [`Token::LineComment`]/
+    /// [`Token::BlockComment`], while `/// ...` and `/** ... */` doc comments are emitted as
+    /// [`Token::DocComment`], all carrying the comment body with its delimiters stripped. By
+    /// default (i.e. via [`Lexer::new`]) comments are skipped, keeping the token stream the
+    /// parser sees unchanged.
+    pub fn with_comments(input: String) -> Self {
+        Lexer {
+            preserve_comments: true,
+            ..Self::new(input)
+        }
+    }
+
+    /// Return the next token in the string, together with its source [`Span`].
+    ///
+    /// This returns `Ok(Some((Token, Span)))` if a token was read, `Ok(None)` if there was no
+    /// more content left to lex, meaning the end of the input was reached (after that, every call
+    /// to `read_token()` will return `Ok(None)`), and `Err(LexError)` if the next token could not
+    /// be lexed at all. A `LexError` does not poison the lexer: calling `read_token()` again
+    /// resumes scanning right after the offending text, though a single malformed character may
+    /// desync later tokens. Use [`Lexer::tokenize_with_errors`] to recover from errors instead.
+    ///
+    /// If this lexer was created with [`Lexer::with_asi`], a line break after a
+    /// statement-ending token may cause this to return a synthesized [`Token::Semicolon`] ahead
+    /// of the next real token, which is then returned (without being re-lexed) on the following
+    /// call.
+    pub fn read_token(&mut self) -> Result<Option<(Token, Span)>, LexError> {
+        if let Some(buffered) = self.asi_buffered.take() {
+            self.asi_prev_ends_statement = Self::ends_statement(&buffered.0);
+            return Ok(Some(buffered));
+        }
+
+        let line_before = self.cursor.line();
+        let next = self.read_token_raw()?;
+
+        if self.asi
+            && self.asi_prev_ends_statement
+            && next.as_ref().is_some_and(|(_, span)| span.line > line_before)
+        {
+            let (token, span) = next.unwrap();
+            let semicolon_span = Span {
+                start: span.start,
+                end: span.start,
+                line: span.line,
+                column: span.column,
+            };
+
+            self.asi_buffered = Some((token, span));
+            self.asi_prev_ends_statement = false;
+            return Ok(Some((Token::Semicolon, semicolon_span)));
+        }
+
+        self.asi_prev_ends_statement = next
+            .as_ref()
+            .is_some_and(|(token, _)| Self::ends_statement(token));
+        Ok(next)
+    }
+
+    /// Whether `token` can legally end a statement, making it eligible for automatic semicolon
+    /// insertion across a line break.
+    fn ends_statement(token: &Token) -> bool {
+        matches!(
+            token,
+            Token::Ident(_)
+                | Token::Integer(_)
+                | Token::Float(_)
+                | Token::RParen
+                | Token::RBracket
+                | Token::Return
+        )
+    }
+
+    /// Return the next token in the string, together with its source [`Span`], ignoring
+    /// automatic semicolon insertion.
+    ///
+    /// This is the actual scanning logic; see [`Lexer::read_token`] for the public API and its
+    /// error/EOF semantics.
+    fn read_token_raw(&mut self) -> Result<Option<(Token, Span)>, LexError> {
         self.consume_whitespace();
 
-        match self.next_char() {
+        let start_idx = self.cursor.pos();
+        let line = self.cursor.line();
+        let column = self.cursor.column();
+
+        let token = match self.cursor.bump() {
             Some('=') => {
-                if self.peek_char() == Some('=') {
-                    self.consume_char();
+                if self.cursor.peek_nth(0) == Some('=') {
+                    self.cursor.bump();
                     Some(Token::Eq)
                 } else {
                     Some(Token::Assign)
@@ -65,8 +167,8 @@ impl Lexer {
             Some('+') => Some(Token::Plus),
             Some('-') => Some(Token::Minus),
             Some('!') => {
-                if self.peek_char() == Some('=') {
-                    self.consume_char();
+                if self.cursor.peek_nth(0) == Some('=') {
+                    self.cursor.bump();
                     Some(Token::NotEq)
                 } else {
                     Some(Token::Bang)
@@ -74,28 +176,60 @@ impl Lexer {
             }
             Some('*') => Some(Token::Asterisk),
             Some('/') => {
-                if self.peek_char() == Some('*') {
-                    self.consume_block_comment();
-                } else if self.peek_char() == Some('/') {
-                    self.consume_line_comment();
+                if self.cursor.peek_nth(0) == Some('*') {
+                    if self.preserve_comments {
+                        let is_doc = self.cursor.peek_nth(1) == Some('*')
+                            && self.cursor.peek_nth(2) != Some('/');
+                        let Some(body) = self.consume_block_comment_capturing(is_doc) else {
+                            return Err(LexError::UnterminatedBlockComment(
+                                self.span_from(start_idx, line, column),
+                            ));
+                        };
+                        Some(if is_doc {
+                            Token::DocComment(body)
+                        } else {
+                            Token::BlockComment(body)
+                        })
+                    } else {
+                        let terminated = self.consume_block_comment();
+                        if !terminated {
+                            return Err(LexError::UnterminatedBlockComment(
+                                self.span_from(start_idx, line, column),
+                            ));
+                        }
+                        // Comments are skipped by default, so restart the scan for a real
+                        // token; this also re-captures the span so it points at whatever
+                        // follows the comment.
+                        return self.read_token_raw();
+                    }
+                } else if self.cursor.peek_nth(0) == Some('/') {
+                    if self.preserve_comments {
+                        let is_doc = self.cursor.peek_nth(1) == Some('/');
+                        let body = self.consume_line_comment_capturing(is_doc);
+                        Some(if is_doc {
+                            Token::DocComment(body)
+                        } else {
+                            Token::LineComment(body)
+                        })
+                    } else {
+                        self.consume_line_comment();
+                        return self.read_token_raw();
+                    }
                 } else {
-                    return Some(Token::Slash);
+                    Some(Token::Slash)
                 }
-
-                // Since the lexer ignores comments we need to call `read_token()` again
-                self.read_token()
             }
             Some('<') => {
-                if self.peek_char() == Some('=') {
-                    self.consume_char();
+                if self.cursor.peek_nth(0) == Some('=') {
+                    self.cursor.bump();
                     Some(Token::LtEq)
                 } else {
                     Some(Token::Lt)
                 }
             }
             Some('>') => {
-                if self.peek_char() == Some('=') {
-                    self.consume_char();
+                if self.cursor.peek_nth(0) == Some('=') {
+                    self.cursor.bump();
                     Some(Token::GtEq)
                 } else {
                     Some(Token::Gt)
@@ -122,156 +256,555 @@ impl Lexer {
                     _ => Some(Token::Ident(ident)),
                 }
             }
-            Some(ch) if ch.is_numeric() => {
-                let number = self.read_number();
-
-                match number {
-                    Ok(num) => Some(Token::Integer(num)),
-                    Err(_) => Some(Token::Invalid),
+            Some(ch) if ch.is_numeric() => match self.read_number() {
+                Ok(token) => Some(token),
+                Err(NumberLexError::Overflow) => {
+                    return Err(LexError::NumberOverflow(
+                        self.span_from(start_idx, line, column),
+                    ));
                 }
-            }
+                Err(NumberLexError::EmptyRadixLiteral) => {
+                    return Err(LexError::EmptyRadixLiteral(
+                        self.span_from(start_idx, line, column),
+                    ));
+                }
+                Err(NumberLexError::AdjacentIdentifier) => {
+                    return Err(LexError::MalformedNumberSuffix(
+                        self.span_from(start_idx, line, column),
+                    ));
+                }
+            },
+            Some('"') => Some(self.read_string(start_idx, line, column)?),
+            Some('\'') => Some(self.read_char(start_idx, line, column)?),
             Some(ch) => {
                 if ch.is_whitespace() {
                     self.consume_whitespace();
-                    self.read_token()
+                    return self.read_token_raw();
                 } else {
-                    Some(Token::Invalid)
+                    return Err(LexError::IllegalCharacter(
+                        ch,
+                        self.span_from(start_idx, line, column),
+                    ));
                 }
             }
             None => None,
-        }
+        };
+
+        let Some(token) = token else {
+            return Ok(None);
+        };
+
+        Ok(Some((token, self.span_from(start_idx, line, column))))
     }
 
-    fn read_number(&mut self) -> anyhow::Result<i32> {
-        let mut number =
-            String::from(self.current_char().expect(
-                "Should be called only after read_token confirmed at least 1 char is valid",
-            ));
+    /// Build the [`Span`] for a token that started at `(start_idx, line, column)` and ends at the
+    /// current cursor position.
+    fn span_from(&self, start_idx: usize, line: usize, column: usize) -> Span {
+        Span {
+            start: start_idx,
+            end: self.cursor.pos(),
+            line,
+            column,
+        }
+    }
 
-        while let Some(peek_ch) = self.peek_char() {
-            if !peek_ch.is_numeric() {
+    /// Resynchronize after a lex error by skipping ahead to the next whitespace or delimiter.
+    ///
+    /// This keeps a single malformed character (or number) from aborting the rest of the scan
+    /// when collecting errors via [`Lexer::tokenize_with_errors`].
+    fn resynchronize(&mut self) {
+        while let Some(ch) = self.cursor.peek_nth(0) {
+            if ch.is_whitespace() || matches!(ch, ',' | ';' | '(' | ')' | '{' | '}' | '[' | ']') {
                 break;
             }
 
-            number.push(self.next_char().expect("The char was peeked"));
+            self.cursor.bump();
+        }
+    }
+
+    /// Lex the whole input, collecting every token and every error instead of stopping at the
+    /// first one.
+    ///
+    /// Encountering a malformed character or literal resynchronizes at the next whitespace or
+    /// delimiter rather than aborting the scan, so a single typo doesn't hide the rest of a
+    /// file's diagnostics. The returned token list always ends with a zero-width [`Token::Eof`].
+    pub fn tokenize_with_errors(input: &str) -> (Vec<(Token, Span)>, Vec<LexError>) {
+        let mut lexer = Lexer::new(input.to_owned());
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+
+        loop {
+            match lexer.read_token() {
+                Ok(Some(pair)) => tokens.push(pair),
+                Ok(None) => break,
+                Err(err) => {
+                    errors.push(err);
+                    lexer.resynchronize();
+                }
+            }
         }
 
-        let number = number.parse().context("read number token")?;
+        let eof_idx = lexer.cursor.char_count();
+        tokens.push((
+            Token::Eof,
+            Span {
+                start: eof_idx,
+                end: eof_idx,
+                line: lexer.cursor.line(),
+                column: lexer.cursor.column(),
+            },
+        ));
 
-        Ok(number)
+        (tokens, errors)
     }
 
-    fn read_identifier(&mut self) -> String {
-        let mut ident =
-            String::from(self.current_char().expect(
+    /// Read an integer or floating-point literal, the first digit having already been consumed.
+    ///
+    /// Handles `0x`/`0b`/`0o` radix prefixes, `_` digit separators, and `digits '.' digits`
+    /// floats, rejecting a literal immediately followed by an identifier character (e.g.
+    /// `42foo`) instead of silently splitting it into two tokens.
+    fn read_number(&mut self) -> Result<Token, NumberLexError> {
+        if self.cursor.current() == Some('0') {
+            let radix = match self.cursor.peek_nth(0) {
+                Some('x') => Some(16),
+                Some('b') => Some(2),
+                Some('o') => Some(8),
+                _ => None,
+            };
+
+            if let Some(radix) = radix {
+                self.cursor.bump();
+                let digits = self.read_radix_digits(radix)?;
+                self.reject_adjacent_identifier()?;
+
+                return i32::from_str_radix(&digits, radix)
+                    .map(Token::Integer)
+                    .map_err(|_| NumberLexError::Overflow);
+            }
+        }
+
+        let mut digits = self.read_decimal_digits();
+
+        let is_float = self.cursor.peek_nth(0) == Some('.')
+            && self.cursor.peek_nth(1).is_some_and(|c| c.is_ascii_digit());
+        if is_float {
+            self.cursor.bump(); // consume the '.'
+            digits.push('.');
+            digits.push(self.cursor.bump().expect("validated by peek_nth(1) above"));
+            digits.push_str(&self.read_digit_run());
+        }
+
+        self.reject_adjacent_identifier()?;
+
+        if is_float {
+            digits
+                .parse()
+                .map(Token::Float)
+                .map_err(|_| NumberLexError::Overflow)
+        } else {
+            digits
+                .parse()
+                .map(Token::Integer)
+                .map_err(|_| NumberLexError::Overflow)
+        }
+    }
+
+    /// Read a run of decimal digits, stripping `_` separators, starting from (and including)
+    /// the current character.
+    fn read_decimal_digits(&mut self) -> String {
+        let mut digits =
+            String::from(self.cursor.current().expect(
                 "Should be called only after read_token confirmed at least 1 char is valid",
             ));
+        digits.push_str(&self.read_digit_run());
 
-        while let Some(peek_ch) = self.peek_char() {
-            if !peek_ch.is_alphanumeric() && peek_ch != '_' {
+        digits
+    }
+
+    /// Consume and return any further run of decimal digits, stripping `_` separators. Unlike
+    /// [`Lexer::read_decimal_digits`], this does not include the current character, so it can
+    /// also be used to continue a literal past a character consumed by the caller (e.g. the
+    /// first digit after a float's `.`).
+    fn read_digit_run(&mut self) -> String {
+        let mut digits = String::new();
+
+        while let Some(peek_ch) = self.cursor.peek_nth(0) {
+            if peek_ch == '_' {
+                self.cursor.bump();
+                continue;
+            }
+
+            if !peek_ch.is_ascii_digit() {
                 break;
             }
 
-            ident.push(self.next_char().expect("The char was peeked"));
+            digits.push(self.cursor.bump().expect("The char was peeked"));
         }
 
-        ident
+        digits
+    }
+
+    /// Read a run of digits valid in `radix` (not including the current character, which is the
+    /// radix prefix letter itself), stripping `_` separators.
+    fn read_radix_digits(&mut self, radix: u32) -> Result<String, NumberLexError> {
+        let mut digits = String::new();
+
+        while let Some(peek_ch) = self.cursor.peek_nth(0) {
+            if peek_ch == '_' {
+                self.cursor.bump();
+                continue;
+            }
+
+            if !peek_ch.is_digit(radix) {
+                break;
+            }
+
+            digits.push(self.cursor.bump().expect("The char was peeked"));
+        }
+
+        if digits.is_empty() {
+            return Err(NumberLexError::EmptyRadixLiteral);
+        }
+
+        Ok(digits)
     }
 
-    fn current_char(&mut self) -> Option<char> {
-        if self.idx >= self.input.len() {
-            return None;
+    /// If the character right after a number literal is an identifier character, consume the
+    /// whole trailing run and report it as malformed (e.g. `42foo`) instead of leaving the
+    /// literal and the identifier to be lexed as two silently-adjacent tokens.
+    fn reject_adjacent_identifier(&mut self) -> Result<(), NumberLexError> {
+        if !self
+            .cursor
+            .peek_nth(0)
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+        {
+            return Ok(());
         }
 
-        Some(self.input[self.idx])
+        while self
+            .cursor
+            .peek_nth(0)
+            .is_some_and(|c| c.is_alphanumeric() || c == '_')
+        {
+            self.cursor.bump();
+        }
+
+        Err(NumberLexError::AdjacentIdentifier)
     }
 
-    fn next_char(&mut self) -> Option<char> {
-        if !self.is_first_char {
-            self.idx += 1;
+    /// Read a `"..."` string literal, the opening quote having already been consumed.
+    ///
+    /// `start_idx`, `line` and `column` are the position of the opening quote, used to anchor
+    /// the span of any error raised while decoding the literal's contents.
+    fn read_string(
+        &mut self,
+        start_idx: usize,
+        line: usize,
+        column: usize,
+    ) -> Result<Token, LexError> {
+        let mut value = String::new();
+        // Keep the *first* error but keep scanning to the closing quote (or EOF) regardless, so
+        // a single bad escape doesn't leave the cursor stranded in the middle of the literal.
+        let mut error = None;
+
+        loop {
+            match self.cursor.bump() {
+                Some('"') => break,
+                Some('\\') => match self.read_escape(start_idx, line, column) {
+                    Ok(ch) => value.push(ch),
+                    Err(err) => {
+                        error.get_or_insert(err);
+                    }
+                },
+                Some(c) => value.push(c),
+                None => {
+                    error.get_or_insert(LexError::UnterminatedString(
+                        self.span_from(start_idx, line, column),
+                    ));
+                    break;
+                }
+            }
         }
-        self.is_first_char = false;
 
-        if self.idx >= self.input.len() {
-            return None;
+        match error {
+            Some(err) => Err(err),
+            None => Ok(Token::Str(value)),
         }
+    }
 
-        let ch = self.input[self.idx];
+    /// Read a `'c'` character literal, the opening quote having already been consumed.
+    ///
+    /// `start_idx`, `line` and `column` are the position of the opening quote, used to anchor
+    /// the span of any error raised while decoding the literal.
+    fn read_char(
+        &mut self,
+        start_idx: usize,
+        line: usize,
+        column: usize,
+    ) -> Result<Token, LexError> {
+        let value = match self.cursor.bump() {
+            Some('\\') => self.read_escape(start_idx, line, column)?,
+            Some('\'') | None => {
+                return Err(LexError::UnterminatedChar(
+                    self.span_from(start_idx, line, column),
+                ));
+            }
+            Some(c) => c,
+        };
 
-        self.column += 1;
-        if ch == '\n' {
-            self.line += 1;
-            self.column = 1;
+        match self.cursor.bump() {
+            Some('\'') => Ok(Token::Char(value)),
+            _ => Err(LexError::UnterminatedChar(
+                self.span_from(start_idx, line, column),
+            )),
         }
+    }
 
-        Some(ch)
+    /// Decode the escape sequence following a `\` inside a string or character literal.
+    ///
+    /// Supports `\\`, `\"`, `\'`, `\n`, `\t`, `\0`, `\xHH` and `\u{...}`/`\uHHHH`.
+    fn read_escape(
+        &mut self,
+        start_idx: usize,
+        line: usize,
+        column: usize,
+    ) -> Result<char, LexError> {
+        match self.cursor.bump() {
+            Some('\\') => Ok('\\'),
+            Some('"') => Ok('"'),
+            Some('\'') => Ok('\''),
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('0') => Ok('\0'),
+            Some('x') => {
+                let digits = self
+                    .cursor
+                    .peek_nth(0)
+                    .filter(char::is_ascii_hexdigit)
+                    .zip(self.cursor.peek_nth(1).filter(char::is_ascii_hexdigit))
+                    .map(|(hi, lo)| String::from_iter([hi, lo]));
+
+                let Some(digits) = digits else {
+                    return Err(LexError::InvalidEscape(
+                        self.span_from(start_idx, line, column),
+                    ));
+                };
+
+                self.cursor.bump();
+                self.cursor.bump();
+
+                let byte = u8::from_str_radix(&digits, 16).expect("validated hex digits");
+                Ok(byte as char)
+            }
+            Some('u') => self.read_unicode_escape(start_idx, line, column),
+            _ => Err(LexError::InvalidEscape(
+                self.span_from(start_idx, line, column),
+            )),
+        }
     }
 
-    fn peek_char(&self) -> Option<char> {
-        if self.idx + 1 >= self.input.len() {
-            return None;
+    /// Decode a `\u{...}` or `\uHHHH` unicode escape, the `\u` having already been consumed.
+    fn read_unicode_escape(
+        &mut self,
+        start_idx: usize,
+        line: usize,
+        column: usize,
+    ) -> Result<char, LexError> {
+        let mut digits = String::new();
+        if self.cursor.peek_nth(0) == Some('{') {
+            self.cursor.bump();
+
+            // Peek before consuming, so a malformed escape never swallows the closing quote.
+            loop {
+                match self.cursor.peek_nth(0) {
+                    Some('}') => {
+                        self.cursor.bump();
+                        break;
+                    }
+                    Some(c) if c.is_ascii_hexdigit() => {
+                        self.cursor.bump();
+                        digits.push(c);
+                    }
+                    _ => {
+                        return Err(LexError::InvalidEscape(
+                            self.span_from(start_idx, line, column),
+                        ));
+                    }
+                }
+            }
+        } else {
+            for _ in 0..4 {
+                match self.cursor.peek_nth(0) {
+                    Some(c) if c.is_ascii_hexdigit() => {
+                        self.cursor.bump();
+                        digits.push(c);
+                    }
+                    _ => {
+                        return Err(LexError::InvalidEscape(
+                            self.span_from(start_idx, line, column),
+                        ));
+                    }
+                }
+            }
         }
 
-        Some(self.input[self.idx + 1])
+        let code = u32::from_str_radix(&digits, 16)
+            .map_err(|_| LexError::InvalidEscape(self.span_from(start_idx, line, column)))?;
+        char::from_u32(code)
+            .ok_or_else(|| LexError::InvalidEscape(self.span_from(start_idx, line, column)))
     }
 
-    fn consume_char(&mut self) {
-        let _ = self.next_char();
+    fn read_identifier(&mut self) -> String {
+        let mut ident =
+            String::from(self.cursor.current().expect(
+                "Should be called only after read_token confirmed at least 1 char is valid",
+            ));
+
+        while let Some(peek_ch) = self.cursor.peek_nth(0) {
+            if !peek_ch.is_alphanumeric() && peek_ch != '_' {
+                break;
+            }
+
+            ident.push(self.cursor.bump().expect("The char was peeked"));
+        }
+
+        ident
     }
 
-    fn consume_block_comment(&mut self) {
-        if self.current_char() != Some('/') && self.peek_char() != Some('*') {
-            return;
+    /// Consume a `/* ... */` comment, returning whether it was properly closed.
+    ///
+    /// Running off the end of the input before the closing `*/` is the caller's cue to surface a
+    /// `LexError::UnterminatedBlockComment` instead of silently swallowing the rest of the file.
+    fn consume_block_comment(&mut self) -> bool {
+        if self.cursor.current() != Some('/') && self.cursor.peek_nth(0) != Some('*') {
+            return true;
         }
 
-        while let Some(c) = self.next_char() {
-            if c == '*' && self.peek_char() == Some('/') {
+        while let Some(c) = self.cursor.bump() {
+            if c == '*' && self.cursor.peek_nth(0) == Some('/') {
                 /* Comments are like this in sol */
-                self.consume_char();
-                return;
+                self.cursor.bump();
+                return true;
             }
         }
+
+        false
     }
 
     fn consume_line_comment(&mut self) {
-        if self.current_char() != Some('/') && self.peek_char() != Some('/') {
+        if self.cursor.current() != Some('/') && self.cursor.peek_nth(0) != Some('/') {
             return;
         }
 
-        while let Some(c) = self.next_char() {
+        while let Some(c) = self.cursor.bump() {
             if c == '\n' {
-                // They can also be like this
-                self.consume_char();
                 return;
             }
         }
     }
 
+    /// Consume a `/* ... */` comment like [`Lexer::consume_block_comment`], but capture and
+    /// return its body (excluding the `/*`/`*/` delimiters, and the extra leading `*` of a
+    /// `/** ... */` doc comment if `skip_doc_marker` is set), or `None` if it was never closed.
+    fn consume_block_comment_capturing(&mut self, skip_doc_marker: bool) -> Option<String> {
+        self.cursor.bump(); // the '*' that opens every block comment
+        if skip_doc_marker {
+            self.cursor.bump(); // the extra '*' that marks a `/**` doc comment
+        }
+
+        let mut body = String::new();
+        while let Some(c) = self.cursor.bump() {
+            if c == '*' && self.cursor.peek_nth(0) == Some('/') {
+                self.cursor.bump();
+                return Some(body);
+            }
+
+            body.push(c);
+        }
+
+        None
+    }
+
+    /// Consume a `// ...` comment like [`Lexer::consume_line_comment`], but capture and return
+    /// its body (excluding the leading `//`, and the extra leading `/` of a `///` doc comment if
+    /// `skip_doc_marker` is set).
+    fn consume_line_comment_capturing(&mut self, skip_doc_marker: bool) -> String {
+        self.cursor.bump(); // the second '/' that opens every line comment
+        if skip_doc_marker {
+            self.cursor.bump(); // the extra '/' that marks a `///` doc comment
+        }
+
+        let mut body = String::new();
+        while let Some(c) = self.cursor.bump() {
+            if c == '\n' {
+                return body;
+            }
+
+            body.push(c);
+        }
+
+        body
+    }
+
     fn consume_whitespace(&mut self) {
-        if let Some(ch) = self.current_char()
+        if let Some(ch) = self.cursor.current()
             && !ch.is_whitespace()
         {
             return;
         }
 
-        while self.peek_char().is_some_and(char::is_whitespace) {
-            self.consume_char();
+        while self.cursor.peek_nth(0).is_some_and(char::is_whitespace) {
+            self.cursor.bump();
         }
     }
 }
 
 impl Iterator for Lexer {
-    type Item = Token;
+    type Item = Result<(Token, Span), LexError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.read_token()
+        self.read_token().transpose()
+    }
+}
+
+/// Lex an entire input string into its tokens, stopping at the first error.
+///
+/// The returned list always ends with a zero-width [`Token::Eof`] marking the end of input, so
+/// callers don't need to special-case running out of tokens. Use
+/// [`Lexer::tokenize_with_errors`] instead if the input may be malformed and every error should
+/// be reported rather than just the first one.
+pub fn lex(input: &str) -> Vec<(Token, Span)> {
+    let mut lexer = Lexer::new(input.to_owned());
+    let mut tokens = Vec::new();
+
+    while let Ok(Some(pair)) = lexer.read_token() {
+        tokens.push(pair);
     }
+
+    // An error stops the scan mid-input; advance to the end so the Eof span's line/column match
+    // its offset.
+    while lexer.cursor.bump().is_some() {}
+
+    let eof_idx = lexer.cursor.char_count();
+    tokens.push((
+        Token::Eof,
+        Span {
+            start: eof_idx,
+            end: eof_idx,
+            line: lexer.cursor.line(),
+            column: lexer.cursor.column(),
+        },
+    ));
+
+    tokens
 }
 
 #[cfg(test)]
 mod tests {
-    use super::tokens::Token;
     use super::Lexer;
+    use super::error::LexError;
+    use super::span::Span;
+    use super::tokens::Token;
 
     #[test]
     fn read_tokens() {
@@ -288,7 +821,7 @@ mod tests {
             Token::Comma,
             Token::Semicolon,
         ];
-        let real_tokens: Vec<Token> = lexer.collect();
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
 
         assert_eq!(expected_tokens, real_tokens);
     }
@@ -318,13 +851,13 @@ mod tests {
             Token::Integer(99),
             Token::Semicolon,
         ];
-        let real_tokens: Vec<Token> = lexer.collect();
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
 
         assert_eq!(expected_tokens, real_tokens);
     }
 
     #[test]
-    fn invalid_token() {
+    fn illegal_characters_are_reported_and_skipped() {
         let input = r#"
             decl a = 42;
             /* this is a comment */
@@ -332,7 +865,9 @@ mod tests {
             // this is another way of commenting
             b = 99;
         "#;
-        let lexer = Lexer::new(input.into());
+
+        let (tokens, errors) = Lexer::tokenize_with_errors(input);
+        let real_tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
 
         let expected_tokens = vec![
             Token::Decl,
@@ -340,18 +875,452 @@ mod tests {
             Token::Assign,
             Token::Integer(42),
             Token::Semicolon,
-            Token::Invalid,
-            Token::Invalid,
-            Token::Invalid,
             Token::Ident(String::from("b")),
             Token::Semicolon,
             Token::Ident(String::from("b")),
             Token::Assign,
             Token::Integer(99),
             Token::Semicolon,
+            Token::Eof,
         ];
-        let real_tokens: Vec<Token> = lexer.collect();
+        assert_eq!(expected_tokens, real_tokens);
+
+        // The whole `^&~` run is a single illegal-character cluster: after reporting `^`, the
+        // lexer resynchronizes at the next whitespace/delimiter instead of re-erroring on `&`
+        // and `~` individually.
+        let illegal_chars: Vec<char> = errors
+            .into_iter()
+            .map(|err| match err {
+                LexError::IllegalCharacter(ch, _) => ch,
+                other => panic!("expected an illegal character error, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(illegal_chars, vec!['^']);
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_reported() {
+        let input = "decl a = 42; /* never closed";
+
+        let (tokens, errors) = Lexer::tokenize_with_errors(input);
+        let real_tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Decl,
+                Token::Ident(String::from("a")),
+                Token::Assign,
+                Token::Integer(42),
+                Token::Semicolon,
+                Token::Eof,
+            ]
+        );
+        assert!(matches!(
+            errors.as_slice(),
+            [LexError::UnterminatedBlockComment(_)]
+        ));
+    }
+
+    #[test]
+    fn token_spans() {
+        let input = "decl a = 42;";
+        let lexer = Lexer::new(input.into());
+
+        let spans: Vec<Span> = lexer.map(|res| res.expect("well-formed input").1).collect();
+
+        assert_eq!(
+            spans[0],
+            Span {
+                start: 0,
+                end: 4,
+                line: 1,
+                column: 1
+            }
+        );
+        assert_eq!(
+            spans[1],
+            Span {
+                start: 5,
+                end: 6,
+                line: 1,
+                column: 6
+            }
+        );
+        assert_eq!(
+            spans[2],
+            Span {
+                start: 7,
+                end: 8,
+                line: 1,
+                column: 8
+            }
+        );
+        assert_eq!(
+            spans[3],
+            Span {
+                start: 9,
+                end: 11,
+                line: 1,
+                column: 10
+            }
+        );
+        assert_eq!(
+            spans[4],
+            Span {
+                start: 11,
+                end: 12,
+                line: 1,
+                column: 12
+            }
+        );
+    }
+
+    #[test]
+    fn lex_appends_eof() {
+        let input = "decl a;";
+        let tokens = super::lex(input);
+
+        let (last_token, last_span) = tokens.last().expect("lex always returns at least EOF");
+        assert_eq!(*last_token, Token::Eof);
+        assert_eq!(last_span.start, last_span.end);
+        assert_eq!(last_span.start, input.len());
+    }
+
+    #[test]
+    fn string_and_char_literals() {
+        let input = r#"decl a = "hi\tthere"; decl b = '\n';"#;
+        let lexer = Lexer::new(input.into());
+
+        let expected_tokens = vec![
+            Token::Decl,
+            Token::Ident(String::from("a")),
+            Token::Assign,
+            Token::Str(String::from("hi\tthere")),
+            Token::Semicolon,
+            Token::Decl,
+            Token::Ident(String::from("b")),
+            Token::Assign,
+            Token::Char('\n'),
+            Token::Semicolon,
+        ];
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
 
         assert_eq!(expected_tokens, real_tokens);
     }
+
+    #[test]
+    fn escape_sequences_are_decoded() {
+        let input = r#""\x41B\u{1F600}""#;
+        let lexer = Lexer::new(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(real_tokens, vec![Token::Str(String::from("AB\u{1F600}"))]);
+    }
+
+    #[test]
+    fn unterminated_string_is_reported() {
+        let input = r#"decl a = "never closed"#;
+
+        let (tokens, errors) = Lexer::tokenize_with_errors(input);
+        let real_tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Decl,
+                Token::Ident(String::from("a")),
+                Token::Assign,
+                Token::Eof
+            ]
+        );
+        assert!(matches!(
+            errors.as_slice(),
+            [LexError::UnterminatedString(_)]
+        ));
+    }
+
+    #[test]
+    fn invalid_escape_is_reported() {
+        let input = r#""bad \q escape""#;
+
+        let (_, errors) = Lexer::tokenize_with_errors(input);
+
+        assert!(matches!(errors.as_slice(), [LexError::InvalidEscape(_)]));
+    }
+
+    #[test]
+    fn malformed_hex_escape_does_not_swallow_the_closing_quote() {
+        let input = r#""\x" 1"#;
+
+        let (tokens, errors) = Lexer::tokenize_with_errors(input);
+        let real_tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
+
+        assert_eq!(real_tokens, vec![Token::Integer(1), Token::Eof]);
+        assert!(matches!(errors.as_slice(), [LexError::InvalidEscape(_)]));
+    }
+
+    #[test]
+    fn radix_prefixed_integers() {
+        let input = "0x2A 0b101 0o17";
+        let lexer = Lexer::new(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![Token::Integer(42), Token::Integer(5), Token::Integer(15)]
+        );
+    }
+
+    #[test]
+    fn digit_separators_are_stripped() {
+        let input = "1_000_000 0x1_000";
+        let lexer = Lexer::new(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![Token::Integer(1_000_000), Token::Integer(0x1000)]
+        );
+    }
+
+    #[test]
+    fn float_literals() {
+        let input = "3.25 0.5";
+        let lexer = Lexer::new(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(real_tokens, vec![Token::Float(3.25), Token::Float(0.5)]);
+    }
+
+    #[test]
+    fn integer_overflow_is_reported() {
+        let input = "99999999999";
+
+        let (_, errors) = Lexer::tokenize_with_errors(input);
+
+        assert!(matches!(errors.as_slice(), [LexError::NumberOverflow(_)]));
+    }
+
+    #[test]
+    fn empty_radix_literal_is_reported() {
+        let input = "0x";
+
+        let (_, errors) = Lexer::tokenize_with_errors(input);
+
+        assert!(matches!(
+            errors.as_slice(),
+            [LexError::EmptyRadixLiteral(_)]
+        ));
+    }
+
+    #[test]
+    fn number_adjacent_to_identifier_is_reported() {
+        let input = "42foo";
+
+        let (tokens, errors) = Lexer::tokenize_with_errors(input);
+        let real_tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
+
+        assert_eq!(real_tokens, vec![Token::Eof]);
+        assert!(matches!(
+            errors.as_slice(),
+            [LexError::MalformedNumberSuffix(_)]
+        ));
+    }
+
+    #[test]
+    fn asi_inserts_semicolons_after_statement_ending_tokens() {
+        let input = "decl a = 42\ndecl b = a\nreturn b";
+        let lexer = Lexer::with_asi(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Decl,
+                Token::Ident(String::from("a")),
+                Token::Assign,
+                Token::Integer(42),
+                Token::Semicolon,
+                Token::Decl,
+                Token::Ident(String::from("b")),
+                Token::Assign,
+                Token::Ident(String::from("a")),
+                Token::Semicolon,
+                Token::Return,
+                Token::Ident(String::from("b")),
+            ]
+        );
+    }
+
+    #[test]
+    fn asi_does_not_insert_after_operators_or_block_openers() {
+        let input = "decl a =\n42;\nif a {\n  a\n}";
+        let lexer = Lexer::with_asi(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Decl,
+                Token::Ident(String::from("a")),
+                Token::Assign,
+                Token::Integer(42),
+                Token::Semicolon,
+                Token::If,
+                Token::Ident(String::from("a")),
+                Token::LBrace,
+                Token::Ident(String::from("a")),
+                Token::Semicolon,
+                Token::RBrace,
+            ]
+        );
+    }
+
+    #[test]
+    fn asi_does_not_trigger_on_a_multiline_token_body() {
+        let input = "a \"x\ny\"";
+        let lexer = Lexer::with_asi(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![Token::Ident(String::from("a")), Token::Str(String::from("x\ny"))]
+        );
+    }
+
+    #[test]
+    fn asi_is_opt_in() {
+        let input = "decl a = 42\ndecl b = a";
+        let lexer = Lexer::new(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Decl,
+                Token::Ident(String::from("a")),
+                Token::Assign,
+                Token::Integer(42),
+                Token::Decl,
+                Token::Ident(String::from("b")),
+                Token::Assign,
+                Token::Ident(String::from("a")),
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_are_skipped_by_default() {
+        let input = "decl a = 42; // a comment\n /* another */ decl b;";
+        let lexer = Lexer::new(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Decl,
+                Token::Ident(String::from("a")),
+                Token::Assign,
+                Token::Integer(42),
+                Token::Semicolon,
+                Token::Decl,
+                Token::Ident(String::from("b")),
+                Token::Semicolon,
+            ]
+        );
+    }
+
+    #[test]
+    fn line_comment_does_not_swallow_the_next_line() {
+        let input = "1 // c\nx";
+        let lexer = Lexer::new(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![Token::Integer(1), Token::Ident(String::from("x"))]
+        );
+    }
+
+    #[test]
+    fn line_comments_are_preserved() {
+        let input = "decl a; // a comment\n ///doc comment\n b;";
+        let lexer = Lexer::with_comments(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Decl,
+                Token::Ident(String::from("a")),
+                Token::Semicolon,
+                Token::LineComment(String::from(" a comment")),
+                Token::DocComment(String::from("doc comment")),
+                Token::Ident(String::from("b")),
+                Token::Semicolon,
+            ]
+        );
+    }
+
+    #[test]
+    fn preserved_line_comment_does_not_swallow_the_next_line() {
+        let input = "1 // c\nx";
+        let lexer = Lexer::with_comments(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Integer(1),
+                Token::LineComment(String::from(" c")),
+                Token::Ident(String::from("x")),
+            ]
+        );
+    }
+
+    #[test]
+    fn block_comments_are_preserved() {
+        let input = "decl a; /* plain */ /** doc */ /**/";
+        let lexer = Lexer::with_comments(input.into());
+
+        let real_tokens: Vec<Token> = lexer.map(|res| res.expect("well-formed input").0).collect();
+
+        assert_eq!(
+            real_tokens,
+            vec![
+                Token::Decl,
+                Token::Ident(String::from("a")),
+                Token::Semicolon,
+                Token::BlockComment(String::from(" plain ")),
+                Token::DocComment(String::from(" doc ")),
+                Token::BlockComment(String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_reported_with_comments_preserved() {
+        let input = "decl a = 42; /* never closed";
+
+        let lexer = Lexer::with_comments(input.into());
+        let last_err = lexer.into_iter().find_map(Result::err);
+
+        assert!(matches!(
+            last_err,
+            Some(LexError::UnterminatedBlockComment(_))
+        ));
+    }
 }
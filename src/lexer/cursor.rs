@@ -0,0 +1,149 @@
+//! An arbitrary-lookahead, backtracking cursor over the input characters.
+//!
+//! This replaces the lexer's old single-char `peek_char`/`next_char` pair (and the `idx`/
+//! `is_first_char` bookkeeping they needed to avoid negative `usize` arithmetic) with a `Cursor`
+//! that can look and seek back any number of characters, which multi-character constructs like
+//! radix prefixes and `\u{...}` escapes need.
+
+/// A forward-scanning, backtrackable cursor over a `Vec<char>`.
+///
+/// `Cursor` tracks the 1-indexed `line`/`column` of the next character to be read, and keeps a
+/// stack of completed lines' lengths (`line_lengths`) so that [`Cursor::seek_back`] can restore
+/// the correct column after rewinding across a newline.
+pub struct Cursor {
+    chars: Vec<char>,
+    /// The index of the next character to be read.
+    pos: usize,
+    line: usize,
+    column: usize,
+    /// The column each completed line ended on, pushed by [`Cursor::bump`] when it consumes a
+    /// newline and popped by [`Cursor::seek_back`] when it rewinds across one.
+    line_lengths: Vec<usize>,
+}
+
+impl Cursor {
+    /// Create a new cursor over `input`.
+    pub fn new(input: &str) -> Self {
+        Cursor {
+            chars: input.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+            line_lengths: Vec::new(),
+        }
+    }
+
+    /// The total number of characters in the input.
+    pub fn char_count(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// The index of the next character to be read, i.e. the number of characters consumed so
+    /// far. Used to compute [`super::span::Span`] boundaries.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The line of the next character to be read (1-indexed).
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column of the next character to be read (1-indexed).
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The last character consumed by [`Cursor::bump`], or `None` if nothing has been read yet.
+    pub fn current(&self) -> Option<char> {
+        self.pos.checked_sub(1).map(|idx| self.chars[idx])
+    }
+
+    /// Look `n` characters ahead without consuming anything; `peek_nth(0)` is the next character
+    /// [`Cursor::bump`] would return.
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).copied()
+    }
+
+    /// Consume and return the next character, updating `line`/`column`.
+    pub fn bump(&mut self) -> Option<char> {
+        let ch = self.chars.get(self.pos).copied()?;
+        self.pos += 1;
+
+        if ch == '\n' {
+            self.line_lengths.push(self.column);
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(ch)
+    }
+
+    /// Rewind the cursor by `n` characters, restoring `line`/`column` as if they had never been
+    /// consumed. `n` must not exceed the number of characters consumed so far.
+    pub fn seek_back(&mut self, n: usize) {
+        for _ in 0..n {
+            self.pos -= 1;
+
+            if self.chars[self.pos] == '\n' {
+                self.line -= 1;
+                self.column = self
+                    .line_lengths
+                    .pop()
+                    .expect("every newline consumed by bump() pushes a matching line length");
+            } else {
+                self.column -= 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seek_back_restores_position_within_a_line() {
+        let mut cursor = Cursor::new("abc");
+        cursor.bump();
+        cursor.bump();
+
+        cursor.seek_back(2);
+
+        assert_eq!(cursor.pos(), 0);
+        assert_eq!(cursor.line(), 1);
+        assert_eq!(cursor.column(), 1);
+    }
+
+    #[test]
+    fn seek_back_restores_the_column_across_a_newline() {
+        let mut cursor = Cursor::new("a\nb");
+        cursor.bump(); // 'a', column 2
+        cursor.bump(); // '\n', line 2, column 1
+        cursor.bump(); // 'b', column 2
+
+        cursor.seek_back(2);
+
+        assert_eq!(cursor.pos(), 1);
+        assert_eq!(cursor.line(), 1);
+        assert_eq!(cursor.column(), 2);
+        assert_eq!(cursor.peek_nth(0), Some('\n'));
+    }
+
+    #[test]
+    fn seek_back_across_several_newlines() {
+        let mut cursor = Cursor::new("ab\ncd\nef");
+        for _ in 0..8 {
+            cursor.bump();
+        }
+
+        cursor.seek_back(6);
+
+        assert_eq!(cursor.pos(), 2);
+        assert_eq!(cursor.line(), 1);
+        assert_eq!(cursor.column(), 3);
+        assert_eq!(cursor.peek_nth(0), Some('\n'));
+    }
+}
@@ -0,0 +1,73 @@
+//! Errors produced while lexing the sol language.
+//!
+//! Unlike the old `Token::Invalid` placeholder, every error here carries the [`Span`] of the
+//! offending text so later stages can point diagnostics at the right place in the source.
+
+use std::fmt;
+
+use super::span::Span;
+
+/// An error produced while lexing a single token.
+#[derive(PartialEq, Debug, Clone)]
+pub enum LexError {
+    /// A character that has no meaning in the sol language (e.g. `^`, `~`).
+    IllegalCharacter(char, Span),
+    /// An integer literal whose value does not fit in an `i32`.
+    NumberOverflow(Span),
+    /// A `/* ... */` comment that was opened but never closed before the end of input.
+    UnterminatedBlockComment(Span),
+    /// A `"..."` string literal that hit the end of input before its closing quote.
+    UnterminatedString(Span),
+    /// A `'c'` character literal that hit the end of input, or held more/less than one
+    /// character, before its closing quote.
+    UnterminatedChar(Span),
+    /// An escape sequence (inside a string or character literal) that sol doesn't recognize,
+    /// e.g. `\q`, `\x` not followed by two hex digits, or an out-of-range `\u{...}`.
+    InvalidEscape(Span),
+    /// A `0x`/`0b`/`0o` radix prefix not followed by at least one valid digit of that radix.
+    EmptyRadixLiteral(Span),
+    /// A number literal immediately followed by an identifier character, e.g. `42foo`.
+    MalformedNumberSuffix(Span),
+}
+
+impl LexError {
+    /// The span of the source text that caused this error.
+    pub fn span(&self) -> Span {
+        match self {
+            LexError::IllegalCharacter(_, span) => *span,
+            LexError::NumberOverflow(span) => *span,
+            LexError::UnterminatedBlockComment(span) => *span,
+            LexError::UnterminatedString(span) => *span,
+            LexError::UnterminatedChar(span) => *span,
+            LexError::InvalidEscape(span) => *span,
+            LexError::EmptyRadixLiteral(span) => *span,
+            LexError::MalformedNumberSuffix(span) => *span,
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexError::IllegalCharacter(ch, _) => write!(f, "illegal character `{ch}`"),
+            LexError::NumberOverflow(_) => write!(f, "number literal does not fit in an i32"),
+            LexError::UnterminatedBlockComment(_) => {
+                write!(f, "unterminated block comment")
+            }
+            LexError::UnterminatedString(_) => write!(f, "unterminated string literal"),
+            LexError::UnterminatedChar(_) => write!(f, "unterminated character literal"),
+            LexError::InvalidEscape(_) => write!(f, "invalid escape sequence"),
+            LexError::EmptyRadixLiteral(_) => {
+                write!(f, "radix prefix is not followed by any valid digit")
+            }
+            LexError::MalformedNumberSuffix(_) => {
+                write!(
+                    f,
+                    "number literal is immediately followed by an identifier character"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
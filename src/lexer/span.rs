@@ -0,0 +1,21 @@
+//! Source spans for the sol language lexer.
+//!
+//! This module contains the [`Span`] type, which records where in the input a token was found so
+//! that later compiler stages (e.g. the parser) can produce diagnostics that point at the
+//! offending source text.
+
+/// The location of a token in the original source string.
+///
+/// `start` and `end` are character offsets into the input (`end` is exclusive), while `line` and
+/// `column` give the human-readable position of the first character of the token.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Span {
+    /// The character offset of the first character of the token.
+    pub start: usize,
+    /// The character offset just past the last character of the token.
+    pub end: usize,
+    /// The line the token starts on (1-indexed).
+    pub line: usize,
+    /// The column the token starts on (1-indexed).
+    pub column: usize,
+}
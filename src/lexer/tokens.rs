@@ -13,17 +13,34 @@ type Name = String;
 /// code for the sol language should be decomposable into a list of these tokens.
 #[derive(PartialEq, Debug)]
 pub enum Token {
-    /// Invalid input.
+    /// The end of the input.
     ///
-    /// An invalid input consists of either characters not present in the sol language (e.g. `~`, `:`)
-    /// or invalid combinations of characters (e.g. `42foo`).
-    Invalid,
+    /// This is a sentinel token emitted by [`lex`](super::lex) once there is no more input left
+    /// to read, so that consumers (e.g. the parser) don't need to special-case running out of
+    /// tokens.
+    Eof,
 
     /// String identifier.
     Ident(Name),
 
     /// Integer literal (i.e. a number).
     Integer(i32),
+    /// Floating-point literal (e.g. `3.14`).
+    Float(f64),
+    /// String literal (i.e. `"..."`), already decoded of its escape sequences.
+    Str(String),
+    /// Character literal (i.e. `'c'`), already decoded of its escape sequence.
+    Char(char),
+
+    /// A `// ...` line comment, with the leading `//` stripped. Only emitted when the lexer was
+    /// created with [`Lexer::with_comments`](super::Lexer::with_comments).
+    LineComment(String),
+    /// A `/* ... */` block comment, with the `/*`/`*/` delimiters stripped. Only emitted when
+    /// the lexer was created with [`Lexer::with_comments`](super::Lexer::with_comments).
+    BlockComment(String),
+    /// A `/// ...` or `/** ... */` doc comment, with its delimiters stripped. Only emitted when
+    /// the lexer was created with [`Lexer::with_comments`](super::Lexer::with_comments).
+    DocComment(String),
 
     /// Assign operator (i.e. `=`).
     Assign,
@@ -86,9 +103,15 @@ pub enum Token {
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Token::Invalid => write!(f, "Invalid"),
+            Token::Eof => write!(f, "EOF"),
             Token::Ident(name) => write!(f, "{name}"),
             Token::Integer(i) => write!(f, "{i}"),
+            Token::Float(n) => write!(f, "{n}"),
+            Token::Str(s) => write!(f, "\"{s}\""),
+            Token::Char(c) => write!(f, "'{c}'"),
+            Token::LineComment(s) => write!(f, "//{s}"),
+            Token::BlockComment(s) => write!(f, "/*{s}*/"),
+            Token::DocComment(s) => write!(f, "///{s}"),
             Token::Assign => write!(f, "="),
             Token::Plus => write!(f, "+"),
             Token::Minus => write!(f, "-"),